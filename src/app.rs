@@ -0,0 +1,362 @@
+use num_complex::Complex64;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+use sdl2::surface::Surface;
+use sdl2::image::SaveSurface;
+use sdl2::video::{FullscreenType, GLProfile, Window};
+use std::cmp;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::fractal::Fractal;
+use crate::hud::{Hud, HudState};
+use crate::render::{self, BoundaryMode, Palette, PixelBuffer, RenderParams, RenderProgress};
+
+const NS_PER_FRAME: i64 = 16_000_000;
+
+/// Builds an `App` from a title, resolution, and the set of fractals it can
+/// cycle between at runtime.
+pub struct AppBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    max_iter: u32,
+    fractals: Vec<Box<dyn Fractal>>,
+}
+
+impl AppBuilder {
+    pub fn new() -> AppBuilder {
+        AppBuilder {
+            title: "fractal-explorer-rs".to_string(),
+            width: 800,
+            height: 600,
+            max_iter: 64,
+            fractals: Vec::new(),
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> AppBuilder {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn resolution(mut self, width: u32, height: u32) -> AppBuilder {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn fractal(mut self, fractal: Box<dyn Fractal>) -> AppBuilder {
+        self.fractals.push(fractal);
+        self
+    }
+
+    pub fn build(self) -> App {
+        assert!(!self.fractals.is_empty(), "AppBuilder requires at least one fractal");
+
+        let sdl = sdl2::init().unwrap();
+        let video_subsystem = sdl.video().unwrap();
+
+        // imgui_opengl_renderer compiles core-profile GLSL and needs a GL
+        // 3.2+ context; request one explicitly instead of trusting the
+        // platform default (e.g. a legacy 2.1 context on macOS), as the
+        // doukutsu-rs imgui overlay this integration is modeled on does.
+        let gl_attr = video_subsystem.gl_attr();
+        gl_attr.set_context_profile(GLProfile::Core);
+        gl_attr.set_context_version(3, 2);
+
+        let window = video_subsystem
+            .window(&self.title, self.width, self.height)
+            .resizable()
+            .opengl()
+            .allow_highdpi()
+            .build()
+            .unwrap();
+
+        let mut canvas = window.into_canvas()
+            .target_texture()
+            .present_vsync()
+            .build()
+            .unwrap();
+
+        let (window_width, window_height) = canvas.output_size().unwrap();
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+
+        let hud = Hud::new(canvas.window(), &video_subsystem);
+
+        let render_params = RenderParams {
+            center: Complex64::new(0.0, 0.0),
+            width: window_width,
+            height: window_height,
+            real_domain: 4.0,
+            max_iter: self.max_iter,
+        };
+
+        App {
+            sdl,
+            canvas,
+            render_params,
+            render_progress: RenderProgress::reset(),
+            fractals: self.fractals,
+            fractal_index: 0,
+            palettes: vec![Palette::classic(), Palette::nes()],
+            palette_index: 0,
+            boundary: BoundaryMode::Clamp,
+            drag_select: None,
+            hud,
+        }
+    }
+}
+
+/// Owns the SDL2 window/canvas and the fractal explorer's state, and drives
+/// the main event + render loop.
+pub struct App {
+    sdl: sdl2::Sdl,
+    canvas: Canvas<Window>,
+    render_params: RenderParams,
+    render_progress: RenderProgress,
+    fractals: Vec<Box<dyn Fractal>>,
+    fractal_index: usize,
+    palettes: Vec<Palette>,
+    palette_index: usize,
+    boundary: BoundaryMode,
+    // Drawable-space (i.e. canvas.output_size()) rubber-band rectangle
+    // (start, current) while a selection drag is in progress.
+    drag_select: Option<((i32, i32), (i32, i32))>,
+    hud: Hud,
+}
+
+impl App {
+    pub fn run(mut self) {
+        let creator = self.canvas.texture_creator();
+        let mut texture: Texture = creator
+            .create_texture_target(PixelFormatEnum::ARGB8888, self.render_params.width, self.render_params.height)
+            .unwrap();
+        let mut pixel_buffer = PixelBuffer::new(self.render_params.width, self.render_params.height);
+
+        let move_up_keys: HashSet<Keycode> = [Keycode::W, Keycode::Up].iter().cloned().collect();
+        let move_left_keys: HashSet<Keycode> = [Keycode::A, Keycode::Left].iter().cloned().collect();
+        let move_down_keys: HashSet<Keycode> = [Keycode::S, Keycode::Down].iter().cloned().collect();
+        let move_right_keys: HashSet<Keycode> = [Keycode::D, Keycode::Right].iter().cloned().collect();
+        let zoom_in_keys: HashSet<Keycode> = [Keycode::I].iter().cloned().collect();
+        let zoom_out_keys: HashSet<Keycode> = [Keycode::O].iter().cloned().collect();
+        let iter_up_keys: HashSet<Keycode> = [Keycode::E].iter().cloned().collect();
+        let iter_down_keys: HashSet<Keycode> = [Keycode::Q].iter().cloned().collect();
+        let render_keys: HashSet<Keycode> = [Keycode::R].iter().cloned().collect();
+        let palette_keys: HashSet<Keycode> = [Keycode::P].iter().cloned().collect();
+        let boundary_keys: HashSet<Keycode> = [Keycode::B].iter().cloned().collect();
+        let fractal_keys: HashSet<Keycode> = [Keycode::F].iter().cloned().collect();
+        let hud_keys: HashSet<Keycode> = [Keycode::H].iter().cloned().collect();
+        let fullscreen_keys: HashSet<Keycode> = [Keycode::G].iter().cloned().collect();
+        let palette_names: Vec<&str> = self.palettes.iter().map(|p| p.name).collect();
+
+        let mut event_pump = self.sdl.event_pump().unwrap();
+        'main: loop {
+            let start_time = Instant::now();
+            let mut new_render_params = RenderParams { ..self.render_params.clone() };
+            let mut explore_mode_changed = false;
+            // Mouse events report logical window coordinates, but render_params
+            // (and therefore screen_to_world) operate in drawable/physical pixels
+            // from canvas.output_size() - the two differ by the DPI scale factor
+            // under allow_highdpi(). Scale every mouse position we read this frame.
+            let (window_width, window_height) = self.canvas.window().size();
+            let (output_width, output_height) = self.canvas.output_size().unwrap();
+            let scale_x = output_width as f64 / window_width.max(1) as f64;
+            let scale_y = output_height as f64 / window_height.max(1) as f64;
+            let to_drawable = |x: i32, y: i32| -> (i32, i32) {
+                ((x as f64 * scale_x) as i32, (y as f64 * scale_y) as i32)
+            };
+
+            let mouse_state = event_pump.mouse_state();
+            let (mouse_x, mouse_y) = to_drawable(mouse_state.x(), mouse_state.y());
+            let (mouse_x, mouse_y) = (mouse_x.max(0) as u32, mouse_y.max(0) as u32);
+            for event in event_pump.poll_iter() {
+                self.hud.handle_event(&event);
+                match event {
+                    Event::Quit { .. } => break 'main,
+                    Event::Window { win_event: WindowEvent::Resized(..), .. } => {
+                        let (new_width, new_height) = self.canvas.output_size().unwrap();
+                        new_render_params.width = new_width;
+                        new_render_params.height = new_height;
+                        texture = creator
+                            .create_texture_target(PixelFormatEnum::ARGB8888, new_render_params.width, new_render_params.height)
+                            .unwrap();
+                        pixel_buffer = PixelBuffer::new(new_render_params.width, new_render_params.height);
+                    },
+                    Event::KeyDown { keycode: key, .. } => {
+                        let key = key.unwrap();
+                        if iter_down_keys.contains(&key) {
+                            new_render_params.max_iter /= 2;
+                            new_render_params.max_iter = cmp::max(new_render_params.max_iter, 1);
+                        } else if iter_up_keys.contains(&key) {
+                            new_render_params.max_iter *= 2;
+                            new_render_params.max_iter = cmp::min(new_render_params.max_iter, 1 << 20);
+                        }
+                        if palette_keys.contains(&key) {
+                            self.palette_index = (self.palette_index + 1) % self.palettes.len();
+                            explore_mode_changed = true;
+                        }
+                        if boundary_keys.contains(&key) {
+                            self.boundary = match self.boundary {
+                                BoundaryMode::Clamp => BoundaryMode::Repeat,
+                                BoundaryMode::Repeat => BoundaryMode::Clamp,
+                            };
+                            explore_mode_changed = true;
+                        }
+                        if fractal_keys.contains(&key) {
+                            self.fractal_index = (self.fractal_index + 1) % self.fractals.len();
+                            explore_mode_changed = true;
+                        }
+                        if hud_keys.contains(&key) {
+                            self.hud.toggle();
+                        }
+                        if fullscreen_keys.contains(&key) {
+                            let new_mode = match self.canvas.window().fullscreen_state() {
+                                FullscreenType::Off => FullscreenType::Desktop,
+                                _ => FullscreenType::Off,
+                            };
+                            self.canvas.window_mut().set_fullscreen(new_mode).unwrap();
+
+                            let (new_width, new_height) = self.canvas.output_size().unwrap();
+                            new_render_params.width = new_width;
+                            new_render_params.height = new_height;
+                            texture = creator
+                                .create_texture_target(PixelFormatEnum::ARGB8888, new_width, new_height)
+                                .unwrap();
+                            pixel_buffer = PixelBuffer::new(new_width, new_height);
+                        }
+                        if render_keys.contains(&key) {
+                            println!("RENDERING TO FILE");
+                            let mut data = pixel_buffer.data.clone();
+                            let surface = Surface::from_data(&mut data, self.render_params.width, self.render_params.height, self.render_params.width * 4, PixelFormatEnum::ARGB8888).unwrap();
+                            surface.save("out.png").unwrap();
+                        }
+                    }
+                    Event::MouseWheel { y, .. } if !self.hud.wants_capture_mouse() => {
+                        if y != 0 {
+                            let world_before = render::screen_to_world(mouse_x, mouse_y, &new_render_params);
+                            let zoom_factor = 0.9f64.powi(y);
+                            new_render_params.real_domain *= zoom_factor;
+                            let world_after = render::screen_to_world(mouse_x, mouse_y, &new_render_params);
+                            new_render_params.center += world_before - world_after;
+                        }
+                    },
+                    Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } if !self.hud.wants_capture_mouse() => {
+                        let (x, y) = to_drawable(x, y);
+                        self.drag_select = Some(((x, y), (x, y)));
+                    },
+                    Event::MouseMotion { x, y, .. } => {
+                        if let Some((start, _)) = self.drag_select {
+                            let (x, y) = to_drawable(x, y);
+                            self.drag_select = Some((start, (x, y)));
+                        }
+                    },
+                    Event::MouseButtonUp { mouse_btn: MouseButton::Left, x, y, .. } => {
+                        if let Some((start, _)) = self.drag_select.take() {
+                            let (x, y) = to_drawable(x, y);
+                            let world_start = render::screen_to_world(start.0.max(0) as u32, start.1.max(0) as u32, &new_render_params);
+                            let world_end = render::screen_to_world(x.max(0) as u32, y.max(0) as u32, &new_render_params);
+                            let selection_width = (x - start.0).unsigned_abs();
+
+                            if selection_width > 1 {
+                                new_render_params.center = (world_start + world_end) / 2.0;
+                                new_render_params.real_domain *= selection_width as f64 / new_render_params.width as f64;
+                            }
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            if !self.hud.wants_capture_keyboard() {
+                let pressed_keys: HashSet<Keycode> = event_pump.keyboard_state().pressed_scancodes().filter_map(Keycode::from_scancode).collect();
+                if pressed_keys.intersection(&move_up_keys).count() > 0 {
+                    new_render_params.center.im -= 0.02 * new_render_params.real_domain;
+                }
+                if pressed_keys.intersection(&move_down_keys).count() > 0 {
+                    new_render_params.center.im += 0.02 * new_render_params.real_domain;
+                }
+                if pressed_keys.intersection(&move_left_keys).count() > 0 {
+                    new_render_params.center.re -= 0.02 * new_render_params.real_domain;
+                }
+                if pressed_keys.intersection(&move_right_keys).count() > 0 {
+                    new_render_params.center.re += 0.02 * new_render_params.real_domain;
+                }
+                if pressed_keys.intersection(&zoom_in_keys).count() > 0 {
+                    new_render_params.real_domain *= 0.95;
+                }
+                if pressed_keys.intersection(&zoom_out_keys).count() > 0 {
+                    new_render_params.real_domain /= 0.95;
+                }
+            }
+
+            if self.render_params != new_render_params || explore_mode_changed {
+                self.render_params = new_render_params;
+                self.render_progress = RenderProgress::reset();
+            }
+
+            if !self.render_progress.done {
+                render::render_pass(
+                    &mut pixel_buffer,
+                    &self.render_params,
+                    self.fractals[self.fractal_index].as_ref(),
+                    &mut self.render_progress,
+                    &self.palettes[self.palette_index],
+                    self.boundary,
+                );
+                texture.update(None, &pixel_buffer.data, pixel_buffer.pitch()).unwrap();
+            }
+
+            self.canvas.clear();
+            let screen_rect = Rect::new(0, 0, self.render_params.width, self.render_params.height);
+            self.canvas.copy(&texture, screen_rect, screen_rect).unwrap();
+
+            if let Some((start, current)) = self.drag_select {
+                let selection_rect = Rect::new(
+                    cmp::min(start.0, current.0),
+                    cmp::min(start.1, current.1),
+                    (start.0 - current.0).unsigned_abs(),
+                    (start.1 - current.1).unsigned_abs(),
+                );
+                self.canvas.set_draw_color(Color::RGB(255, 255, 255));
+                self.canvas.draw_rect(selection_rect).unwrap();
+            }
+
+            let hud_state = HudState {
+                max_iter: self.render_params.max_iter,
+                palette_index: self.palette_index,
+                boundary: self.boundary,
+            };
+            let hud_state = self.hud.render(
+                self.canvas.window(),
+                &event_pump,
+                &self.render_params,
+                &self.render_progress,
+                &palette_names,
+                hud_state,
+            );
+            if hud_state.max_iter != self.render_params.max_iter
+                || hud_state.palette_index != self.palette_index
+                || hud_state.boundary != self.boundary
+            {
+                self.render_params.max_iter = hud_state.max_iter;
+                self.palette_index = hud_state.palette_index;
+                self.boundary = hud_state.boundary;
+                self.render_progress = RenderProgress::reset();
+            }
+
+            self.canvas.present();
+
+            let elapsed_ns = start_time.elapsed().as_nanos() as i64;
+            let time_to_sleep = NS_PER_FRAME - elapsed_ns;
+            if time_to_sleep > 0 {
+                ::std::thread::sleep(Duration::from_nanos(time_to_sleep as u64));
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use num_complex::Complex64;
+use std::ops::{Add, Mul};
+
+const BAILOUT: f64 = (1u32 << 16) as f64;
+
+/// A pluggable escape-time formula. `escape` is given the world-space point
+/// under the pixel (as `z_0`) and returns the smooth (fractional) escape
+/// value, or `f64::INFINITY` if the point never escapes within `max_iter`
+/// iterations.
+pub trait Fractal: Sync {
+    fn escape(&self, z_0: Complex64, max_iter: u32) -> f64;
+}
+
+/// `z_{n+1} = z_n^power + z_0`, i.e. the classic Mandelbrot set generalized
+/// to higher powers.
+pub struct Mandelbrot {
+    pub power: u32,
+}
+
+impl Mandelbrot {
+    pub fn new() -> Mandelbrot {
+        Mandelbrot { power: 2 }
+    }
+
+    pub fn with_power(power: u32) -> Mandelbrot {
+        Mandelbrot { power }
+    }
+}
+
+impl Fractal for Mandelbrot {
+    fn escape(&self, z_0: Complex64, max_iter: u32) -> f64 {
+        escape_time(z_0, z_0, self.power, max_iter)
+    }
+}
+
+/// `z_{n+1} = z_n^power + c` for a fixed `c`, iterating the pixel itself as
+/// `z_0`.
+pub struct Julia {
+    pub c: Complex64,
+    pub power: u32,
+}
+
+impl Julia {
+    pub fn new(c: Complex64) -> Julia {
+        Julia { c, power: 2 }
+    }
+}
+
+impl Fractal for Julia {
+    fn escape(&self, z_0: Complex64, max_iter: u32) -> f64 {
+        escape_time(z_0, self.c, self.power, max_iter)
+    }
+}
+
+fn complex_powu(z: Complex64, power: u32) -> Complex64 {
+    let mut result = Complex64::new(1.0, 0.0);
+    for _ in 0..power {
+        result = result.mul(z);
+    }
+    return result;
+}
+
+fn escape_time(z_0: Complex64, c: Complex64, power: u32, max_iter: u32) -> f64 {
+    let mut z = z_0;
+    let mut n = 0;
+    while z.norm_sqr() < BAILOUT && n < max_iter {
+        z = complex_powu(z, power).add(c);
+        n += 1;
+    }
+
+    if n == max_iter {
+        return f64::INFINITY;
+    }
+
+    return n as f64 + 1.0 - (0.5 * z.norm_sqr().ln()).ln() / (power as f64).ln();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complex_powu_matches_repeated_multiplication() {
+        let z = Complex64::new(1.5, -0.5);
+        assert_eq!(complex_powu(z, 1), z);
+        assert_eq!(complex_powu(z, 2), z * z);
+        assert_eq!(complex_powu(z, 3), z * z * z);
+    }
+
+    #[test]
+    fn escape_time_interior_point_never_escapes() {
+        let mu = escape_time(Complex64::new(0.0, 0.0), Complex64::new(0.0, 0.0), 2, 100);
+        assert!(mu.is_infinite());
+    }
+
+    #[test]
+    fn escape_time_far_point_escapes_before_max_iter() {
+        let mu = escape_time(Complex64::new(10.0, 10.0), Complex64::new(10.0, 10.0), 2, 100);
+        assert!(mu.is_finite());
+    }
+}
@@ -0,0 +1,130 @@
+use imgui::{Condition, Context};
+use imgui_opengl_renderer::Renderer as GlRenderer;
+use imgui_sdl2::ImguiSdl2;
+use sdl2::event::Event;
+use sdl2::video::Window;
+use sdl2::{EventPump, VideoSubsystem};
+
+use crate::render::{self, BoundaryMode, RenderParams, RenderProgress};
+
+/// The subset of app state the HUD can read and edit. Returned from
+/// `Hud::render` so the caller can diff it against the live state and decide
+/// whether to kick off a fresh render.
+pub struct HudState {
+    pub max_iter: u32,
+    pub palette_index: usize,
+    pub boundary: BoundaryMode,
+}
+
+/// Toggleable heads-up display drawn on top of the fractal each frame,
+/// backed by imgui's SDL2 + OpenGL integration (mirrors the doukutsu-rs
+/// imgui overlay setup: an `ImguiSdl2` platform backend feeding SDL2 events
+/// into an `imgui::Context`, rendered with `imgui_opengl_renderer`).
+pub struct Hud {
+    imgui: Context,
+    platform: ImguiSdl2,
+    renderer: GlRenderer,
+    visible: bool,
+}
+
+impl Hud {
+    pub fn new(window: &Window, video_subsystem: &VideoSubsystem) -> Hud {
+        let mut imgui = Context::create();
+        imgui.set_ini_filename(None);
+
+        let platform = ImguiSdl2::new(&mut imgui, window);
+        let renderer = GlRenderer::new(&mut imgui, |s| video_subsystem.gl_get_proc_address(s) as _);
+
+        Hud {
+            imgui,
+            platform,
+            renderer,
+            visible: true,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        self.platform.handle_event(&mut self.imgui, event);
+    }
+
+    pub fn wants_capture_mouse(&self) -> bool {
+        self.imgui.io().want_capture_mouse
+    }
+
+    pub fn wants_capture_keyboard(&self) -> bool {
+        self.imgui.io().want_capture_keyboard
+    }
+
+    // Draws the HUD panel (if visible) and returns the possibly-edited
+    // control state. Must be called after `canvas.copy` and before
+    // `canvas.present` so the overlay lands on top of the fractal but still
+    // makes it into the swapped frame - and never into a saved PNG export,
+    // which reads from the plain pixel buffer instead.
+    pub fn render(
+        &mut self,
+        window: &Window,
+        event_pump: &EventPump,
+        render_params: &RenderParams,
+        render_progress: &RenderProgress,
+        palette_names: &[&str],
+        mut state: HudState,
+    ) -> HudState {
+        if !self.visible {
+            return state;
+        }
+
+        self.platform.prepare_frame(self.imgui.io_mut(), window, &event_pump.mouse_state());
+        let ui = self.imgui.frame();
+
+        ui.window("fractal-explorer-rs")
+            .position([10.0, 10.0], Condition::FirstUseEver)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(format!("center: {:.6} {:+.6}i", render_params.center.re, render_params.center.im));
+                ui.text(format!("zoom: {:.3}x", 4.0 / render_params.real_domain));
+                let elapsed = render_progress.start_time.elapsed().as_secs_f64();
+                if render_progress.done {
+                    ui.text(format!("status: done ({:.2}s)", elapsed));
+                } else {
+                    let total_tiles = render::tile_count(render_params.height);
+                    let percent = render_progress.fraction_done(total_tiles) * 100.0;
+                    ui.text(format!("status: rendering {:.0}% ({:.1}s)", percent, elapsed));
+                }
+                ui.separator();
+
+                let mut max_iter = state.max_iter as i32;
+                if ui.slider("max_iter", 1, 1 << 20, &mut max_iter) {
+                    state.max_iter = max_iter as u32;
+                }
+
+                let mut palette_index = state.palette_index as i32;
+                if ui.slider("palette", 0, palette_names.len() as i32 - 1, &mut palette_index) {
+                    state.palette_index = palette_index as usize;
+                }
+                ui.text(format!("  {}", palette_names[state.palette_index]));
+
+                let boundary_label = match state.boundary {
+                    BoundaryMode::Clamp => "boundary: clamp",
+                    BoundaryMode::Repeat => "boundary: repeat",
+                };
+                if ui.button(boundary_label) {
+                    state.boundary = match state.boundary {
+                        BoundaryMode::Clamp => BoundaryMode::Repeat,
+                        BoundaryMode::Repeat => BoundaryMode::Clamp,
+                    };
+                }
+
+                ui.separator();
+                ui.text("WASD/arrows pan, I/O zoom, Q/E iter, F fractal, H hide HUD");
+            });
+
+        self.platform.prepare_render(&ui, window);
+        self.renderer.render(&mut self.imgui);
+
+        state
+    }
+}
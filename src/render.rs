@@ -0,0 +1,312 @@
+use num_complex::Complex64;
+use rayon::prelude::*;
+use std::cmp;
+use std::time::{Duration, Instant};
+
+use crate::fractal::Fractal;
+
+const BYTES_PER_PIXEL: usize = 4;
+const TILE_ROWS: u32 = 64;
+const INTERIOR_COLOR: Rgb = Rgb { r: 0, g: 0, b: 0 };
+const INITIAL_BLOCK_SIZE: u32 = 128;
+// Wall-clock budget for a single render_pass call, so a deep zoom at a high
+// max_iter spreads its work across many frames instead of freezing the event
+// loop for however long the full buffer would otherwise take.
+const FRAME_TIME_BUDGET: Duration = Duration::from_millis(8);
+
+#[derive(PartialEq, Clone)]
+pub struct RenderParams {
+    pub center: Complex64,
+    pub width: u32,
+    pub height: u32,
+    pub real_domain: f64,
+    pub max_iter: u32,
+}
+
+/// Tracks progress through the coarse-to-fine `block_size` passes `render_pass`
+/// runs to refine a frame, plus how far the current pass has gotten within
+/// `render_pass`'s own `FRAME_TIME_BUDGET`-bounded calls.
+pub struct RenderProgress {
+    pub done: bool,
+    pub block_size: u32,
+    tile_cursor: usize,
+    pub start_time: Instant,
+}
+
+impl RenderProgress {
+    pub fn reset() -> RenderProgress {
+        RenderProgress {
+            done: false,
+            block_size: INITIAL_BLOCK_SIZE,
+            tile_cursor: 0,
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Fraction (0.0-1.0) of every `block_size` pass completed so far, for
+    /// progress reporting in the HUD. `total_tiles` should come from
+    /// `tile_count` for the buffer height currently being rendered.
+    pub fn fraction_done(&self, total_tiles: usize) -> f64 {
+        if self.done || total_tiles == 0 {
+            return 1.0;
+        }
+
+        let total_passes = (INITIAL_BLOCK_SIZE.trailing_zeros() + 1) as usize;
+        let passes_done = (INITIAL_BLOCK_SIZE / self.block_size).trailing_zeros() as usize;
+        let tiles_done = passes_done * total_tiles + self.tile_cursor;
+        (tiles_done as f64 / (total_passes * total_tiles) as f64).min(1.0)
+    }
+}
+
+/// CPU-side ARGB8888 pixel buffer. Rendered to in parallel, then uploaded to
+/// the texture once per frame with `Texture::update`.
+pub struct PixelBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl PixelBuffer {
+    pub fn new(width: u32, height: u32) -> PixelBuffer {
+        PixelBuffer {
+            width,
+            height,
+            data: vec![0u8; (width * height) as usize * BYTES_PER_PIXEL],
+        }
+    }
+
+    pub fn pitch(&self) -> usize {
+        self.width as usize * BYTES_PER_PIXEL
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    fn lerp(a: Rgb, b: Rgb, t: f64) -> Rgb {
+        Rgb {
+            r: (a.r as f64 + (b.r as f64 - a.r as f64) * t) as u8,
+            g: (a.g as f64 + (b.g as f64 - a.g as f64) * t) as u8,
+            b: (a.b as f64 + (b.b as f64 - a.b as f64) * t) as u8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BoundaryMode {
+    Clamp,
+    Repeat,
+}
+
+/// Maps a smooth escape value `mu` to a color by scaling it into the
+/// control-color list and linearly interpolating between neighbors.
+pub struct Palette {
+    pub name: &'static str,
+    colors: Vec<Rgb>,
+    scale: f64,
+}
+
+impl Palette {
+    pub fn color_at(&self, mu: f64, boundary: BoundaryMode) -> Rgb {
+        let segments = (self.colors.len() - 1) as f64;
+        let mut t = mu * self.scale;
+        t = match boundary {
+            BoundaryMode::Clamp => t.max(0.0).min(segments),
+            BoundaryMode::Repeat => (t / segments).fract().rem_euclid(1.0) * segments,
+        };
+
+        let index = (t.floor() as usize).min(self.colors.len() - 2);
+        let frac = t - index as f64;
+        Rgb::lerp(self.colors[index], self.colors[index + 1], frac)
+    }
+
+    pub fn classic() -> Palette {
+        Palette {
+            name: "classic",
+            colors: vec![
+                Rgb { r: 0, g: 7, b: 100 },
+                Rgb { r: 32, g: 107, b: 203 },
+                Rgb { r: 237, g: 255, b: 255 },
+                Rgb { r: 255, g: 170, b: 0 },
+                Rgb { r: 0, g: 2, b: 0 },
+            ],
+            scale: 0.06,
+        }
+    }
+
+    // A 64-entry RGB ramp built from the classic NES master palette, for a
+    // colorful, strongly-banded alternative to the smooth `classic` ramp.
+    pub fn nes() -> Palette {
+        Palette {
+            name: "nes",
+            colors: NES_PALETTE.iter().map(|&(r, g, b)| Rgb { r, g, b }).collect(),
+            scale: 0.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_lerp_midpoint() {
+        let a = Rgb { r: 0, g: 0, b: 0 };
+        let b = Rgb { r: 100, g: 200, b: 50 };
+        let mid = Rgb::lerp(a, b, 0.5);
+        assert_eq!((mid.r, mid.g, mid.b), (50, 100, 25));
+    }
+
+    #[test]
+    fn color_at_clamp_pins_to_last_color_past_the_end() {
+        let palette = Palette::classic();
+        let last = *palette.colors.last().unwrap();
+        let color = palette.color_at(1e9, BoundaryMode::Clamp);
+        assert_eq!((color.r, color.g, color.b), (last.r, last.g, last.b));
+    }
+
+    #[test]
+    fn color_at_repeat_wraps_around_every_full_cycle() {
+        let palette = Palette::classic();
+        let segments = (palette.colors.len() - 1) as f64;
+        let period = segments / palette.scale;
+
+        let base = palette.color_at(0.1, BoundaryMode::Repeat);
+        let wrapped = palette.color_at(0.1 + period, BoundaryMode::Repeat);
+        assert_eq!((base.r, base.g, base.b), (wrapped.r, wrapped.g, wrapped.b));
+    }
+}
+
+const NES_PALETTE: [(u8, u8, u8); 64] = [
+    (84, 84, 84), (0, 30, 116), (8, 16, 144), (48, 0, 136),
+    (68, 0, 100), (92, 0, 48), (84, 4, 0), (60, 24, 0),
+    (32, 42, 0), (8, 58, 0), (0, 64, 0), (0, 60, 0),
+    (0, 50, 60), (0, 0, 0), (0, 0, 0), (0, 0, 0),
+    (152, 150, 152), (8, 76, 196), (48, 50, 236), (92, 30, 228),
+    (136, 20, 176), (160, 20, 100), (152, 34, 32), (120, 60, 0),
+    (84, 90, 0), (40, 114, 0), (8, 124, 0), (0, 118, 40),
+    (0, 102, 120), (0, 0, 0), (0, 0, 0), (0, 0, 0),
+    (236, 238, 236), (76, 154, 236), (120, 124, 236), (176, 98, 236),
+    (228, 84, 236), (236, 88, 180), (236, 106, 100), (212, 136, 32),
+    (160, 170, 0), (116, 196, 0), (76, 208, 32), (56, 204, 108),
+    (56, 180, 204), (60, 60, 60), (0, 0, 0), (0, 0, 0),
+    (236, 238, 236), (168, 204, 236), (188, 188, 236), (212, 178, 236),
+    (236, 174, 236), (236, 174, 212), (236, 180, 176), (228, 196, 144),
+    (204, 210, 120), (180, 222, 120), (168, 226, 144), (152, 226, 180),
+    (160, 214, 228), (160, 162, 160), (0, 0, 0), (0, 0, 0),
+];
+
+pub fn screen_to_world(x: u32, y: u32, render_params: &RenderParams) -> Complex64 {
+    let w = render_params.width as f64;
+    let h = render_params.height as f64;
+    let x = x as f64;
+    let y = y as f64;
+    let complex_domain = render_params.real_domain * h / w;
+
+    let world_re = render_params.center.re + render_params.real_domain * (x - (w / 2.0)) / w;
+    let world_im = render_params.center.im + complex_domain * (y - (h / 2.0)) / h;
+    return Complex64{re: world_re, im:world_im};
+}
+
+/// Number of `TILE_ROWS`-high tiles `render_pass` divides a buffer of the
+/// given height into. Exposed so callers (the HUD) can turn a
+/// `RenderProgress` into a completion fraction via `RenderProgress::fraction_done`.
+pub fn tile_count(height: u32) -> usize {
+    let tile_height = TILE_ROWS.min(height.max(1));
+    height.max(1).div_ceil(tile_height) as usize
+}
+
+/// Renders tiles of the current `progress.block_size` pass, starting where
+/// the previous call left off, until either the pass completes or
+/// `FRAME_TIME_BUDGET` elapses - whichever comes first. A tile that's still
+/// in progress when the budget runs out is left for the next call to redo
+/// from its own top (cheap and idempotent, since block colors are
+/// deterministic), which keeps the event loop responsive no matter how
+/// large `render_params.max_iter` is.
+pub fn render_pass(
+    buffer: &mut PixelBuffer,
+    render_params: &RenderParams,
+    fractal: &dyn Fractal,
+    progress: &mut RenderProgress,
+    palette: &Palette,
+    boundary: BoundaryMode,
+) {
+    if progress.done {
+        return;
+    }
+
+    let width = buffer.width;
+    let height = buffer.height;
+    // Clamp the stride used for chunking (not `buffer`'s actual dimensions)
+    // so a zero-width buffer can't drive `par_chunks_mut` into a zero chunk
+    // size - it still renders nothing, since `width` itself stays 0 below.
+    let pitch = cmp::max(buffer.pitch(), BYTES_PER_PIXEL);
+    let tile_height = TILE_ROWS.min(height.max(1));
+    let block_size = progress.block_size;
+    let deadline = Instant::now() + FRAME_TIME_BUDGET;
+
+    let first_unfinished_tile = buffer.data
+        .par_chunks_mut(pitch * tile_height as usize)
+        .enumerate()
+        .skip(progress.tile_cursor)
+        .map(|(tile_index, tile)| {
+            let tile_tl_y = tile_index as u32 * tile_height;
+            let tile_rows = (tile.len() / pitch) as u32;
+
+            let mut block_y = tile_tl_y - (tile_tl_y % block_size);
+            while block_y < tile_tl_y + tile_rows {
+                let mut block_x = 0;
+                while block_x < width {
+                    if Instant::now() >= deadline {
+                        return Some(tile_index);
+                    }
+
+                    let sample_x = cmp::min(block_x + block_size / 2, width - 1);
+                    let sample_y = cmp::min(block_y + block_size / 2, height - 1);
+
+                    let z_0 = screen_to_world(sample_x, sample_y, render_params);
+                    let mu = fractal.escape(z_0, render_params.max_iter);
+                    let color = if mu.is_infinite() {
+                        INTERIOR_COLOR
+                    } else {
+                        palette.color_at(mu, boundary)
+                    };
+
+                    let row_end = cmp::min(block_y + block_size, tile_tl_y + tile_rows);
+                    let col_end = cmp::min(block_x + block_size, width);
+                    for y in cmp::max(block_y, tile_tl_y)..row_end {
+                        let row_offset = (y - tile_tl_y) as usize * pitch;
+                        for x in block_x..col_end {
+                            let offset = row_offset + x as usize * BYTES_PER_PIXEL;
+                            tile[offset] = color.b;
+                            tile[offset + 1] = color.g;
+                            tile[offset + 2] = color.r;
+                            tile[offset + 3] = 255;
+                        }
+                    }
+
+                    block_x += block_size;
+                }
+                block_y += block_size;
+            }
+            None
+        })
+        .filter_map(|unfinished_tile| unfinished_tile)
+        .min();
+
+    match first_unfinished_tile {
+        Some(tile_index) => progress.tile_cursor = tile_index,
+        None => {
+            progress.tile_cursor = 0;
+            progress.block_size /= 2;
+            if progress.block_size < 1 {
+                progress.done = true;
+            }
+        }
+    }
+}